@@ -4,18 +4,29 @@ use std::panic;
 use std::ptr;
 use std::str::FromStr;
 use std::fs;
-use std::path::PathBuf; // Path is unused
+use std::path::{Path, PathBuf};
 use std::env;
 
 // Ethereum-specific types from ethers crate
-use ethers::types::{Address, U256, Bytes as EthersBytes, TransactionRequest, Signature as EthersSignature}; // H256 unused
-use ethers::signers::{LocalWallet, Signer};
+use ethers::types::{Address, U256, H256, Bytes as EthersBytes, TransactionRequest, Signature as EthersSignature};
+use ethers::types::transaction::eip1559::Eip1559TransactionRequest;
+use ethers::types::transaction::eip2930::{AccessList, AccessListItem};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::signers::{LocalWallet, Signer, MnemonicBuilder};
+use ethers::signers::coins_bip39::{English, Mnemonic};
 
 // k256 for direct crypto operations if LocalWallet isn't used for all parts
 use k256::ecdsa::SigningKey;
+use k256::{SecretKey, PublicKey};
+use k256::ecdh::diffie_hellman;
+use k256::elliptic_curve::sec1::ToEncodedPoint;
 // generic_array is used by k256::SigningKey::from_bytes
 use generic_array::{GenericArray, typenum::U32};
 
+// keccak256 is used for the V3 keystore MAC;
+// hash_message applies the EIP-191 personal_sign prefix.
+use ethers::utils::{keccak256, hash_message};
+
 // Dependencies for secure key storage
 use aes_gcm::{Aes256Gcm, Key, Nonce}; // Key and Nonce are re-exported GenericArray wrappers
 use aes_gcm::aead::Aead; 
@@ -26,6 +37,14 @@ use rand::rngs::OsRng; // For generating cryptographically secure random numbers
 use rand::RngCore; // Trait for Rngs like OsRng
 
 use serde::{Serialize, Deserialize};
+use serde_json::json;
+
+// KDFs and stream cipher for the Web3 Secret Storage (V3) keystore format
+use aes::Aes128;
+use aes::cipher::{KeyIvInit, StreamCipher};
+use ctr::Ctr128BE;
+use hmac::{Hmac, Mac};
+use sha2::{Sha256, Digest};
 
 // For directory/file operations
 use directories::ProjectDirs;
@@ -35,6 +54,7 @@ const KEY_STORE_DIR_NAME: &str = "juliaos_secure_keys";
 const MASTER_PASSWORD_ENV_VAR: &str = "JULIAOS_KEYSTORE_PASSWORD";
 const AES_NONCE_SIZE: usize = 12; // 96 bits for AES-GCM
 const ARGON2_SALT_SIZE: usize = 16; // 16 bytes for Argon2 salt
+const DEFAULT_DERIVATION_PATH: &str = "m/44'/60'/0'/0/0"; // BIP-44 Ethereum account 0
 
 #[derive(Serialize, Deserialize)]
 struct EncryptedKeyFile {
@@ -101,6 +121,46 @@ fn encrypt_pk_and_prepare_file_data(pk_bytes: &[u8; 32], master_password: &str)
 }
 
 
+// --- Helper: Restrict a key file to the owning user only ---
+#[cfg(unix)]
+fn set_key_file_permissions(path: &Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+        .map_err(|e| format!("Failed to set key file permissions: {}", e))
+}
+
+#[cfg(windows)]
+fn set_key_file_permissions(path: &Path) -> Result<(), String> {
+    use std::process::Command;
+    // Equivalent of 0600 on Windows: drop inherited ACLs and grant only the
+    // current user full control via icacls.
+    let user = env::var("USERNAME").map_err(|_| "USERNAME env var not set".to_string())?;
+    let path_str = path.to_str().ok_or_else(|| "Non-UTF8 key file path".to_string())?;
+    let status = Command::new("icacls")
+        .args([path_str, "/inheritance:r", "/grant:r", &format!("{}:F", user)])
+        .status()
+        .map_err(|e| format!("Failed to run icacls: {}", e))?;
+    if !status.success() {
+        return Err("icacls failed to restrict key file permissions".to_string());
+    }
+    Ok(())
+}
+
+// --- Helper: Atomically write a key file with restrictive permissions ---
+// Writes to a sibling temp file, tightens its permissions, then renames into
+// place so a crash can never leave a half-written keystore behind.
+fn write_key_file_atomic(final_path: &Path, contents: &str) -> Result<(), String> {
+    let tmp_path = final_path.with_extension("json.tmp");
+    fs::write(&tmp_path, contents)
+        .map_err(|e| format!("Failed to write temporary key file: {}", e))?;
+    if let Err(e) = set_key_file_permissions(&tmp_path) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+    fs::rename(&tmp_path, final_path)
+        .map_err(|e| format!("Failed to finalize key file: {}", e))
+}
+
 // --- Helper: Load and decrypt private key ---
 fn load_and_decrypt_pk(key_identifier: &str, master_password: &str) -> Result<[u8; 32], String> {
     let key_store_path = get_key_storage_path()?;
@@ -152,6 +212,51 @@ fn c_str_to_string(c_str_ptr: *const c_char) -> Result<String, String> {
     }
 }
 
+// --- Helper: copy a Rust string into the caller-provided C output buffer ---
+// Mirrors the inline logic in sign_evm_transaction_ffi so the newer entrypoints
+// don't each re-implement the bounds check + null termination. Returns the number
+// of bytes written (excluding the terminator) on success, or a negative FFI error
+// code on failure.
+fn write_cstring_to_out(s: &str, out_ptr: *mut c_char, out_buffer_len: c_uint) -> Result<c_int, c_int> {
+    if s.len() + 1 > out_buffer_len as usize {
+        eprintln!("Output buffer too small. Needed: {}, Available: {}", s.len() + 1, out_buffer_len);
+        return Err(-3);
+    }
+    let c_string = match CString::new(s) {
+        Ok(cs) => cs,
+        Err(_) => { eprintln!("Failed to create CString for output buffer."); return Err(-4); }
+    };
+    unsafe {
+        ptr::copy_nonoverlapping(c_string.as_ptr(), out_ptr, s.len());
+        *out_ptr.add(s.len()) = 0;
+    }
+    Ok(s.len() as c_int)
+}
+
+// --- Helper: constant-time byte comparison for MAC/integrity checks ---
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut diff = 0u8;
+    for (x, y) in a.iter().zip(b.iter()) {
+        diff |= x ^ y;
+    }
+    diff == 0
+}
+
+// --- Helper: parse a 0x-prefixed (or bare) hex string into a U256 ---
+fn parse_hex_u256(s: &str) -> Result<U256, String> {
+    U256::from_str_radix(s.strip_prefix("0x").unwrap_or(s), 16)
+        .map_err(|e| format!("Error parsing hex value '{}': {}", s, e))
+}
+
+// --- Helper: parse a 0x-prefixed (or bare) hex string into an Address ---
+fn parse_hex_address(s: &str) -> Result<Address, String> {
+    Address::from_str(s.strip_prefix("0x").unwrap_or(s))
+        .map_err(|e| format!("Error parsing address '{}': {}", s, e))
+}
+
 // --- FFI Function Definition ---
 #[no_mangle]
 pub extern "C" fn sign_evm_transaction_ffi(
@@ -276,6 +381,157 @@ pub extern "C" fn sign_evm_transaction_ffi(
     }
 }
 
+// --- Helper: parse an EIP-2930 access list from a compact string form ---
+//
+// Format: entries separated by ';', each entry `address=key1,key2,...`. The
+// storage-key list may be empty (`address=`). An empty input yields an empty
+// access list. Addresses and keys are 0x-prefixed (or bare) hex.
+fn parse_access_list(s: &str) -> Result<AccessList, String> {
+    let mut items = Vec::new();
+    for entry in s.split(';') {
+        let entry = entry.trim();
+        if entry.is_empty() {
+            continue;
+        }
+        let (addr_part, keys_part) = match entry.split_once('=') {
+            Some((a, k)) => (a, k),
+            None => (entry, ""),
+        };
+        let address = parse_hex_address(addr_part.trim())?;
+        let mut storage_keys = Vec::new();
+        for key in keys_part.split(',') {
+            let key = key.trim();
+            if key.is_empty() {
+                continue;
+            }
+            let key_bytes = hex::decode(key.strip_prefix("0x").unwrap_or(key))
+                .map_err(|e| format!("Error decoding storage key '{}': {}", key, e))?;
+            if key_bytes.len() != 32 {
+                return Err(format!("Storage key must be 32 bytes, got {}", key_bytes.len()));
+            }
+            storage_keys.push(H256::from_slice(&key_bytes));
+        }
+        items.push(AccessListItem { address, storage_keys });
+    }
+    Ok(AccessList(items))
+}
+
+/// Sign an EIP-2930 (type-1) or EIP-1559 (type-2) transaction. `tx_type` selects the
+/// variant: `1` for EIP-2930 (uses `gas_price_wei_hex`), `2` for EIP-1559 (uses
+/// `max_fee_per_gas_wei_hex`/`max_priority_fee_per_gas_wei_hex`). Both accept an
+/// `access_list` in the compact `addr=key1,key2;addr2=...` form (empty for none).
+/// The returned signed hex carries the correct `0x01`/`0x02` type prefix.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub extern "C" fn sign_evm_transaction_typed_ffi(
+    tx_type_c: c_uint,
+    key_identifier_cchar: *const c_char,
+    to_cchar: *const c_char,
+    value_wei_hex_cchar: *const c_char,
+    data_hex_cchar: *const c_char,
+    nonce_c: c_ulonglong,
+    gas_price_wei_hex_cchar: *const c_char,
+    max_fee_per_gas_wei_hex_cchar: *const c_char,
+    max_priority_fee_per_gas_wei_hex_cchar: *const c_char,
+    access_list_cchar: *const c_char,
+    gas_limit_c: c_ulonglong,
+    chain_id_c: c_ulonglong,
+    signed_tx_hex_out_ptr: *mut c_char,
+    out_buffer_len_c: c_uint,
+) -> c_int {
+    let result = panic::catch_unwind(|| {
+        let key_id = match c_str_to_string(key_identifier_cchar) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("Error converting key_identifier: {}", e); return -4; }
+        };
+
+        let master_password = match env::var(MASTER_PASSWORD_ENV_VAR) {
+            Ok(pass) => pass,
+            Err(_) => {
+                eprintln!("Master password ENV var '{}' not set.", MASTER_PASSWORD_ENV_VAR);
+                return -1;
+            }
+        };
+
+        let pk_bytes_array = match load_and_decrypt_pk(&key_id, &master_password) {
+            Ok(pk) => pk,
+            Err(e) => { eprintln!("Failed to load/decrypt private key for '{}': {}", key_id, e); return -1; }
+        };
+        let signing_key_k256 = match SigningKey::from_bytes(GenericArray::<u8, U32>::from_slice(&pk_bytes_array)) {
+            Ok(key) => key,
+            Err(_) => { eprintln!("Failed to create k256::SigningKey from decrypted bytes."); return -1; }
+        };
+        let wallet = LocalWallet::from(signing_key_k256).with_chain_id(chain_id_c);
+
+        // Shared fields.
+        let to_str = match c_str_to_string(to_cchar) { Ok(s) => s, Err(e) => { eprintln!("{}", e); return -4; } };
+        let to_addr = match parse_hex_address(&to_str) { Ok(a) => a, Err(e) => { eprintln!("{}", e); return -4; } };
+        let value_str = match c_str_to_string(value_wei_hex_cchar) { Ok(s) => s, Err(e) => { eprintln!("{}", e); return -4; } };
+        let value_u256 = match parse_hex_u256(&value_str) { Ok(v) => v, Err(e) => { eprintln!("{}", e); return -4; } };
+        let data_str = match c_str_to_string(data_hex_cchar) { Ok(s) => s, Err(e) => { eprintln!("{}", e); return -4; } };
+        let data_bytes_vec = match hex::decode(data_str.strip_prefix("0x").unwrap_or(&data_str)) {
+            Ok(b) => b,
+            Err(e) => { eprintln!("Error decoding data_hex '{}': {}", data_str, e); return -4; }
+        };
+        let data_ethers_bytes = EthersBytes::from(data_bytes_vec);
+        let access_list_str = match c_str_to_string(access_list_cchar) { Ok(s) => s, Err(e) => { eprintln!("{}", e); return -4; } };
+        let access_list = match parse_access_list(&access_list_str) { Ok(al) => al, Err(e) => { eprintln!("{}", e); return -4; } };
+
+        let typed_tx: TypedTransaction = match tx_type_c {
+            2 => {
+                let max_fee_str = match c_str_to_string(max_fee_per_gas_wei_hex_cchar) { Ok(s) => s, Err(e) => { eprintln!("{}", e); return -4; } };
+                let max_fee = match parse_hex_u256(&max_fee_str) { Ok(v) => v, Err(e) => { eprintln!("{}", e); return -4; } };
+                let max_prio_str = match c_str_to_string(max_priority_fee_per_gas_wei_hex_cchar) { Ok(s) => s, Err(e) => { eprintln!("{}", e); return -4; } };
+                let max_prio = match parse_hex_u256(&max_prio_str) { Ok(v) => v, Err(e) => { eprintln!("{}", e); return -4; } };
+                Eip1559TransactionRequest::new()
+                    .to(to_addr)
+                    .value(value_u256)
+                    .data(data_ethers_bytes)
+                    .nonce(U256::from(nonce_c))
+                    .gas(U256::from(gas_limit_c))
+                    .max_fee_per_gas(max_fee)
+                    .max_priority_fee_per_gas(max_prio)
+                    .access_list(access_list)
+                    .chain_id(chain_id_c)
+                    .into()
+            }
+            1 => {
+                let gas_price_str = match c_str_to_string(gas_price_wei_hex_cchar) { Ok(s) => s, Err(e) => { eprintln!("{}", e); return -4; } };
+                let gas_price = match parse_hex_u256(&gas_price_str) { Ok(v) => v, Err(e) => { eprintln!("{}", e); return -4; } };
+                let tx_request = TransactionRequest::new()
+                    .to(to_addr)
+                    .value(value_u256)
+                    .data(data_ethers_bytes)
+                    .nonce(U256::from(nonce_c))
+                    .gas_price(gas_price)
+                    .gas(U256::from(gas_limit_c))
+                    .chain_id(chain_id_c);
+                TypedTransaction::Eip2930(ethers::types::transaction::eip2930::Eip2930TransactionRequest {
+                    tx: tx_request,
+                    access_list,
+                })
+            }
+            other => { eprintln!("Unsupported tx_type: {} (expected 1 or 2)", other); return -4; }
+        };
+
+        let signature: EthersSignature = match wallet.sign_transaction_sync(&typed_tx) {
+            Ok(sig) => sig,
+            Err(e) => { eprintln!("Error signing transaction: {}", e); return -2; }
+        };
+        // TypedTransaction::rlp_signed prepends the 0x01/0x02 envelope type byte.
+        let signed_tx_hex = format!("0x{}", hex::encode(typed_tx.rlp_signed(&signature)));
+
+        match write_cstring_to_out(&signed_tx_hex, signed_tx_hex_out_ptr, out_buffer_len_c) {
+            Ok(len) => len,
+            Err(code) => code,
+        }
+    });
+    match result {
+        Ok(val) => val,
+        Err(_) => { eprintln!("Panic caught in sign_evm_transaction_typed_ffi"); -5 }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn store_new_key_ffi(
     key_identifier_cchar: *const c_char,
@@ -319,10 +575,9 @@ pub extern "C" fn store_new_key_ffi(
             return -16; // Key already exists
         }
 
-        match fs::write(&key_file_path, json_data) {
+        match write_key_file_atomic(&key_file_path, &json_data) {
             Ok(_) => {
                 println!("Successfully stored new encrypted key for identifier: {}", key_id);
-                // TODO: Set file permissions to be restrictive (e.g., 600)
                 0 // Success
             }
             Err(e) => {
@@ -338,6 +593,930 @@ pub extern "C" fn store_new_key_ffi(
 }
 
 
+// ---------------------------------------------------------------------------
+// Web3 Secret Storage (V3) keystore interop
+//
+// The canonical geth/parity keystore is a JSON object holding an `address`, a
+// `version` (3), and a `crypto`/`Crypto` block describing an AES-128-CTR
+// ciphertext together with the KDF used to turn the password into the 32-byte
+// derived key. We decrypt such files so existing keys can be brought into our
+// Argon2id store, and we emit them so keys can be handed back to standard
+// Ethereum tooling.
+// ---------------------------------------------------------------------------
+
+type Aes128Ctr = Ctr128BE<Aes128>;
+
+#[derive(Serialize, Deserialize)]
+struct V3CipherParams {
+    iv: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct V3Crypto {
+    cipher: String,
+    cipherparams: V3CipherParams,
+    ciphertext: String,
+    kdf: String,
+    kdfparams: serde_json::Value,
+    mac: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct V3KeyStore {
+    #[serde(default)]
+    address: Option<String>,
+    // geth writes lower-case `crypto`, older parity files use `Crypto`.
+    #[serde(alias = "Crypto")]
+    crypto: V3Crypto,
+    version: u32,
+}
+
+// --- Helper: fetch a JSON field from a kdfparams object as the expected type ---
+fn kdfparam_str(params: &serde_json::Value, key: &str) -> Result<String, String> {
+    params.get(key)
+        .and_then(|v| v.as_str())
+        .map(String::from)
+        .ok_or_else(|| format!("Missing or invalid kdfparams field: {}", key))
+}
+
+fn kdfparam_u64(params: &serde_json::Value, key: &str) -> Result<u64, String> {
+    params.get(key)
+        .and_then(|v| v.as_u64())
+        .ok_or_else(|| format!("Missing or invalid kdfparams field: {}", key))
+}
+
+// --- Helper: derive the 32-byte key from a V3 crypto block and password ---
+fn derive_v3_key(crypto: &V3Crypto, password: &str) -> Result<[u8; 32], String> {
+    let params = &crypto.kdfparams;
+    let dklen = kdfparam_u64(params, "dklen")? as usize;
+    if dklen != 32 {
+        return Err(format!("Unsupported V3 dklen: {} (expected 32)", dklen));
+    }
+    let salt = hex::decode(kdfparam_str(params, "salt")?.trim_start_matches("0x"))
+        .map_err(|e| format!("Failed to decode kdf salt: {}", e))?;
+
+    let mut derived = [0u8; 32];
+    match crypto.kdf.as_str() {
+        "scrypt" => {
+            let n = kdfparam_u64(params, "n")?;
+            let r = kdfparam_u64(params, "r")? as u32;
+            let p = kdfparam_u64(params, "p")? as u32;
+            // scrypt crate takes log2(n) as the cost parameter.
+            if n == 0 || (n & (n - 1)) != 0 {
+                return Err(format!("scrypt n must be a power of two, got {}", n));
+            }
+            let log_n = n.trailing_zeros() as u8;
+            let scrypt_params = scrypt::Params::new(log_n, r, p, dklen)
+                .map_err(|e| format!("Invalid scrypt params: {}", e))?;
+            scrypt::scrypt(password.as_bytes(), &salt, &scrypt_params, &mut derived)
+                .map_err(|e| format!("scrypt derivation failed: {}", e))?;
+        }
+        "pbkdf2" => {
+            let prf = kdfparam_str(params, "prf")?;
+            if prf != "hmac-sha256" {
+                return Err(format!("Unsupported pbkdf2 prf: {}", prf));
+            }
+            let c = kdfparam_u64(params, "c")? as u32;
+            pbkdf2::pbkdf2::<Hmac<Sha256>>(password.as_bytes(), &salt, c, &mut derived)
+                .map_err(|e| format!("pbkdf2 derivation failed: {}", e))?;
+        }
+        other => return Err(format!("Unsupported V3 kdf: {}", other)),
+    }
+    Ok(derived)
+}
+
+// --- Helper: decrypt a V3 keystore JSON string into raw 32-byte private key ---
+fn decrypt_v3_keystore(keystore_json: &str, password: &str) -> Result<[u8; 32], String> {
+    let store: V3KeyStore = serde_json::from_str(keystore_json)
+        .map_err(|e| format!("Failed to parse V3 keystore JSON: {}", e))?;
+    if store.version != 3 {
+        return Err(format!("Unsupported keystore version: {}", store.version));
+    }
+    if store.crypto.cipher != "aes-128-ctr" {
+        return Err(format!("Unsupported cipher: {}", store.crypto.cipher));
+    }
+
+    let derived = derive_v3_key(&store.crypto, password)?;
+    let ciphertext = hex::decode(store.crypto.ciphertext.trim_start_matches("0x"))
+        .map_err(|e| format!("Failed to decode ciphertext: {}", e))?;
+
+    // MAC = keccak256(derivedKey[16..32] ++ ciphertext).
+    let mut mac_input = Vec::with_capacity(16 + ciphertext.len());
+    mac_input.extend_from_slice(&derived[16..32]);
+    mac_input.extend_from_slice(&ciphertext);
+    let computed_mac = keccak256(&mac_input);
+    let stored_mac = hex::decode(store.crypto.mac.trim_start_matches("0x"))
+        .map_err(|e| format!("Failed to decode mac: {}", e))?;
+    if !constant_time_eq(&computed_mac, &stored_mac) {
+        return Err("MAC mismatch: wrong password or corrupt keystore".to_string());
+    }
+
+    let iv = hex::decode(store.crypto.cipherparams.iv.trim_start_matches("0x"))
+        .map_err(|e| format!("Failed to decode iv: {}", e))?;
+    if iv.len() != 16 {
+        return Err(format!("Invalid iv length: {}", iv.len()));
+    }
+
+    let mut plaintext = ciphertext;
+    let mut cipher = Aes128Ctr::new(derived[..16].into(), iv.as_slice().into());
+    cipher.apply_keystream(&mut plaintext);
+
+    if plaintext.len() != 32 {
+        return Err(format!("Decrypted key is not 32 bytes: {}", plaintext.len()));
+    }
+    let mut pk = [0u8; 32];
+    pk.copy_from_slice(&plaintext);
+    Ok(pk)
+}
+
+// --- Helper: encrypt a raw private key into a V3 keystore JSON string ---
+fn encrypt_v3_keystore(pk_bytes: &[u8; 32], password: &str) -> Result<String, String> {
+    // Standard geth scrypt cost parameters.
+    const N: u64 = 262144;
+    const R: u32 = 8;
+    const P: u32 = 1;
+
+    let mut salt = [0u8; 32];
+    OsRng.fill_bytes(&mut salt);
+    let mut iv = [0u8; 16];
+    OsRng.fill_bytes(&mut iv);
+
+    let log_n = N.trailing_zeros() as u8;
+    let scrypt_params = scrypt::Params::new(log_n, R, P, 32)
+        .map_err(|e| format!("Invalid scrypt params: {}", e))?;
+    let mut derived = [0u8; 32];
+    scrypt::scrypt(password.as_bytes(), &salt, &scrypt_params, &mut derived)
+        .map_err(|e| format!("scrypt derivation failed: {}", e))?;
+
+    let mut ciphertext = pk_bytes.to_vec();
+    let mut cipher = Aes128Ctr::new(derived[..16].into(), iv.as_slice().into());
+    cipher.apply_keystream(&mut ciphertext);
+
+    let mut mac_input = Vec::with_capacity(16 + ciphertext.len());
+    mac_input.extend_from_slice(&derived[16..32]);
+    mac_input.extend_from_slice(&ciphertext);
+    let mac = keccak256(&mac_input);
+
+    // Derive the canonical lower-case address (no 0x) from the private key.
+    let signing_key = SigningKey::from_bytes(GenericArray::<u8, U32>::from_slice(pk_bytes))
+        .map_err(|_| "Invalid private key bytes".to_string())?;
+    let address = LocalWallet::from(signing_key).address();
+
+    let keystore = json!({
+        "address": hex::encode(address.as_bytes()),
+        "version": 3,
+        "crypto": {
+            "cipher": "aes-128-ctr",
+            "cipherparams": { "iv": hex::encode(iv) },
+            "ciphertext": hex::encode(&ciphertext),
+            "kdf": "scrypt",
+            "kdfparams": {
+                "dklen": 32,
+                "n": N,
+                "r": R,
+                "p": P,
+                "salt": hex::encode(salt),
+            },
+            "mac": hex::encode(mac),
+        }
+    });
+    serde_json::to_string_pretty(&keystore)
+        .map_err(|e| format!("Failed to serialize V3 keystore: {}", e))
+}
+
+/// Import a standard Web3 Secret Storage (V3) keystore JSON blob, decrypt it with
+/// `keystore_password`, and persist the recovered key into our Argon2id store under
+/// `key_identifier` encrypted with `master_password`.
+#[no_mangle]
+pub extern "C" fn import_v3_keystore_ffi(
+    key_identifier_cchar: *const c_char,
+    keystore_json_cchar: *const c_char,
+    keystore_password_cchar: *const c_char,
+    master_password_cchar: *const c_char,
+) -> c_int {
+    let result = panic::catch_unwind(|| {
+        let key_id = match c_str_to_string(key_identifier_cchar) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("Error converting key_identifier for import: {}", e); return -11; }
+        };
+        let keystore_json = match c_str_to_string(keystore_json_cchar) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("Error converting keystore JSON: {}", e); return -4; }
+        };
+        let keystore_password = match c_str_to_string(keystore_password_cchar) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("Error converting keystore password: {}", e); return -4; }
+        };
+        let master_password = match c_str_to_string(master_password_cchar) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("Error converting master_password for import: {}", e); return -12; }
+        };
+
+        let pk_bytes = match decrypt_v3_keystore(&keystore_json, &keystore_password) {
+            Ok(pk) => pk,
+            Err(e) => { eprintln!("Failed to decrypt V3 keystore for '{}': {}", key_id, e); return -18; }
+        };
+
+        let encrypted_file_data = match encrypt_pk_and_prepare_file_data(&pk_bytes, &master_password) {
+            Ok(data) => data,
+            Err(e) => { eprintln!("Failed to encrypt imported key for '{}': {}", key_id, e); return -13; }
+        };
+        let json_data = match serde_json::to_string_pretty(&encrypted_file_data) {
+            Ok(json) => json,
+            Err(e) => { eprintln!("Failed to serialize imported key data for '{}': {}", key_id, e); return -14; }
+        };
+        let key_store_path = match get_key_storage_path() {
+            Ok(path) => path,
+            Err(e) => { eprintln!("Failed to get key_storage_path for import: {}", e); return -15; }
+        };
+        let key_file_path = key_store_path.join(format!("{}.json", key_id));
+        if key_file_path.exists() {
+            eprintln!("Key file already exists for identifier: {}. Will not overwrite.", key_id);
+            return -16;
+        }
+        match write_key_file_atomic(&key_file_path, &json_data) {
+            Ok(_) => {
+                println!("Successfully imported V3 keystore for identifier: {}", key_id);
+                0
+            }
+            Err(e) => { eprintln!("Failed to write imported key file for '{}': {}", key_id, e); -17 }
+        }
+    });
+    match result {
+        Ok(val) => val,
+        Err(_) => { eprintln!("Panic caught in import_v3_keystore_ffi"); -5 }
+    }
+}
+
+/// Export the stored key `key_identifier` (decrypted with `master_password`) as a
+/// canonical V3 keystore JSON blob protected by `keystore_password`, written to the
+/// caller-provided output buffer.
+#[no_mangle]
+pub extern "C" fn export_v3_keystore_ffi(
+    key_identifier_cchar: *const c_char,
+    master_password_cchar: *const c_char,
+    keystore_password_cchar: *const c_char,
+    keystore_json_out_ptr: *mut c_char,
+    out_buffer_len_c: c_uint,
+) -> c_int {
+    let result = panic::catch_unwind(|| {
+        let key_id = match c_str_to_string(key_identifier_cchar) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("Error converting key_identifier for export: {}", e); return -11; }
+        };
+        let master_password = match c_str_to_string(master_password_cchar) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("Error converting master_password for export: {}", e); return -12; }
+        };
+        let keystore_password = match c_str_to_string(keystore_password_cchar) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("Error converting keystore password: {}", e); return -4; }
+        };
+
+        let pk_bytes = match load_and_decrypt_pk(&key_id, &master_password) {
+            Ok(pk) => pk,
+            Err(e) => { eprintln!("Failed to load/decrypt key for '{}': {}", key_id, e); return -1; }
+        };
+        let keystore_json = match encrypt_v3_keystore(&pk_bytes, &keystore_password) {
+            Ok(json) => json,
+            Err(e) => { eprintln!("Failed to build V3 keystore for '{}': {}", key_id, e); return -18; }
+        };
+
+        match write_cstring_to_out(&keystore_json, keystore_json_out_ptr, out_buffer_len_c) {
+            Ok(len) => len,
+            Err(code) => code,
+        }
+    });
+    match result {
+        Ok(val) => val,
+        Err(_) => { eprintln!("Panic caught in export_v3_keystore_ffi"); -5 }
+    }
+}
+
+
+/// Produce an EIP-191 `personal_sign` signature over `message_bytes[..len]` using the
+/// key stored under `key_identifier` (master password taken from the environment).
+/// Writes the 65-byte `r||s||v` signature as a 0x-prefixed hex string.
+#[no_mangle]
+pub extern "C" fn sign_message_ffi(
+    key_identifier_cchar: *const c_char,
+    message_bytes: *const u8,
+    message_len_c: c_ulonglong,
+    signature_hex_out_ptr: *mut c_char,
+    out_buffer_len_c: c_uint,
+) -> c_int {
+    let result = panic::catch_unwind(|| {
+        let key_id = match c_str_to_string(key_identifier_cchar) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("Error converting key_identifier: {}", e); return -4; }
+        };
+        if message_bytes.is_null() {
+            eprintln!("Null pointer passed for message bytes.");
+            return -4;
+        }
+        let message = unsafe { std::slice::from_raw_parts(message_bytes, message_len_c as usize) };
+
+        let master_password = match env::var(MASTER_PASSWORD_ENV_VAR) {
+            Ok(pass) => pass,
+            Err(_) => { eprintln!("Master password ENV var '{}' not set.", MASTER_PASSWORD_ENV_VAR); return -1; }
+        };
+        let pk_bytes_array = match load_and_decrypt_pk(&key_id, &master_password) {
+            Ok(pk) => pk,
+            Err(e) => { eprintln!("Failed to load/decrypt private key for '{}': {}", key_id, e); return -1; }
+        };
+        let signing_key_k256 = match SigningKey::from_bytes(GenericArray::<u8, U32>::from_slice(&pk_bytes_array)) {
+            Ok(key) => key,
+            Err(_) => { eprintln!("Failed to create k256::SigningKey from decrypted bytes."); return -1; }
+        };
+        let wallet = LocalWallet::from(signing_key_k256);
+
+        let hash = hash_message(message);
+        let signature = match wallet.sign_hash(hash) {
+            Ok(sig) => sig,
+            Err(e) => { eprintln!("Error signing message: {}", e); return -2; }
+        };
+        let signature_hex = format!("0x{}", hex::encode(signature.to_vec()));
+
+        match write_cstring_to_out(&signature_hex, signature_hex_out_ptr, out_buffer_len_c) {
+            Ok(len) => len,
+            Err(code) => code,
+        }
+    });
+    match result {
+        Ok(val) => val,
+        Err(_) => { eprintln!("Panic caught in sign_message_ffi"); -5 }
+    }
+}
+
+/// Recover the signer's 20-byte address from an EIP-191 `personal_sign` signature
+/// (`signature_hex`, 65-byte r||s||v) over `message_bytes[..len]`, so callers can
+/// verify attestations without the private key. Writes the 0x-prefixed address hex.
+#[no_mangle]
+pub extern "C" fn recover_address_ffi(
+    message_bytes: *const u8,
+    message_len_c: c_ulonglong,
+    signature_hex_cchar: *const c_char,
+    address_hex_out_ptr: *mut c_char,
+    out_buffer_len_c: c_uint,
+) -> c_int {
+    let result = panic::catch_unwind(|| {
+        if message_bytes.is_null() {
+            eprintln!("Null pointer passed for message bytes.");
+            return -4;
+        }
+        let message = unsafe { std::slice::from_raw_parts(message_bytes, message_len_c as usize) };
+        let signature_hex = match c_str_to_string(signature_hex_cchar) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("Error converting signature hex: {}", e); return -4; }
+        };
+        let sig_bytes = match hex::decode(signature_hex.strip_prefix("0x").unwrap_or(&signature_hex)) {
+            Ok(b) => b,
+            Err(e) => { eprintln!("Error decoding signature hex: {}", e); return -4; }
+        };
+        let signature = match EthersSignature::try_from(sig_bytes.as_slice()) {
+            Ok(sig) => sig,
+            Err(e) => { eprintln!("Error parsing signature: {}", e); return -4; }
+        };
+        let address = match signature.recover(message.to_vec()) {
+            Ok(addr) => addr,
+            Err(e) => { eprintln!("Error recovering address: {}", e); return -2; }
+        };
+        let address_hex = format!("0x{}", hex::encode(address.as_bytes()));
+
+        match write_cstring_to_out(&address_hex, address_hex_out_ptr, out_buffer_len_c) {
+            Ok(len) => len,
+            Err(code) => code,
+        }
+    });
+    match result {
+        Ok(val) => val,
+        Err(_) => { eprintln!("Panic caught in recover_address_ffi"); -5 }
+    }
+}
+
+
+/// Derive an account from a BIP-39 `mnemonic` (with optional BIP-39 `passphrase`)
+/// along the BIP-32 `derivation_path` (empty string defaults to `m/44'/60'/0'/0/0`),
+/// then store the child private key through the existing encrypt-and-write pipeline
+/// under `key_identifier`, protected by `master_password`.
+#[no_mangle]
+pub extern "C" fn store_mnemonic_account_ffi(
+    key_identifier_cchar: *const c_char,
+    mnemonic_cchar: *const c_char,
+    passphrase_cchar: *const c_char,
+    derivation_path_cchar: *const c_char,
+    master_password_cchar: *const c_char,
+) -> c_int {
+    let result = panic::catch_unwind(|| {
+        let key_id = match c_str_to_string(key_identifier_cchar) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("Error converting key_identifier for mnemonic store: {}", e); return -11; }
+        };
+        let mnemonic = match c_str_to_string(mnemonic_cchar) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("Error converting mnemonic: {}", e); return -4; }
+        };
+        let passphrase = match c_str_to_string(passphrase_cchar) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("Error converting passphrase: {}", e); return -4; }
+        };
+        let derivation_path = match c_str_to_string(derivation_path_cchar) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("Error converting derivation_path: {}", e); return -4; }
+        };
+        let master_password = match c_str_to_string(master_password_cchar) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("Error converting master_password for mnemonic store: {}", e); return -12; }
+        };
+
+        let path = if derivation_path.trim().is_empty() {
+            DEFAULT_DERIVATION_PATH
+        } else {
+            derivation_path.trim()
+        };
+
+        let builder = match MnemonicBuilder::<English>::default()
+            .phrase(mnemonic.as_str())
+            .derivation_path(path)
+        {
+            Ok(b) => b.password(passphrase.as_str()),
+            Err(e) => { eprintln!("Invalid derivation path '{}': {}", path, e); return -4; }
+        };
+        let wallet = match builder.build() {
+            Ok(w) => w,
+            Err(e) => { eprintln!("Failed to derive wallet from mnemonic: {}", e); return -18; }
+        };
+        let pk_bytes: [u8; 32] = wallet.signer().to_bytes().into();
+
+        let encrypted_file_data = match encrypt_pk_and_prepare_file_data(&pk_bytes, &master_password) {
+            Ok(data) => data,
+            Err(e) => { eprintln!("Failed to encrypt mnemonic key for '{}': {}", key_id, e); return -13; }
+        };
+        let json_data = match serde_json::to_string_pretty(&encrypted_file_data) {
+            Ok(json) => json,
+            Err(e) => { eprintln!("Failed to serialize mnemonic key data for '{}': {}", key_id, e); return -14; }
+        };
+        let key_store_path = match get_key_storage_path() {
+            Ok(path) => path,
+            Err(e) => { eprintln!("Failed to get key_storage_path for mnemonic store: {}", e); return -15; }
+        };
+        let key_file_path = key_store_path.join(format!("{}.json", key_id));
+        if key_file_path.exists() {
+            eprintln!("Key file already exists for identifier: {}. Will not overwrite.", key_id);
+            return -16;
+        }
+        match write_key_file_atomic(&key_file_path, &json_data) {
+            Ok(_) => {
+                println!("Successfully stored mnemonic-derived key for identifier: {}", key_id);
+                0
+            }
+            Err(e) => { eprintln!("Failed to write mnemonic key file for '{}': {}", key_id, e); -17 }
+        }
+    });
+    match result {
+        Ok(val) => val,
+        Err(_) => { eprintln!("Panic caught in store_mnemonic_account_ffi"); -5 }
+    }
+}
+
+/// Generate a fresh BIP-39 mnemonic of `word_count` words (12 or 24) so users get a
+/// backup-able phrase, written to the caller-provided output buffer.
+#[no_mangle]
+pub extern "C" fn generate_mnemonic_ffi(
+    word_count_c: c_uint,
+    mnemonic_out_ptr: *mut c_char,
+    out_buffer_len_c: c_uint,
+) -> c_int {
+    let result = panic::catch_unwind(|| {
+        let word_count = word_count_c as usize;
+        if word_count != 12 && word_count != 24 {
+            eprintln!("Unsupported word count: {} (expected 12 or 24)", word_count);
+            return -4;
+        }
+        let mnemonic = match Mnemonic::<English>::new_with_count(&mut OsRng, word_count) {
+            Ok(m) => m,
+            Err(e) => { eprintln!("Failed to generate mnemonic: {}", e); return -2; }
+        };
+        let phrase = match mnemonic.to_phrase() {
+            Ok(p) => p,
+            Err(e) => { eprintln!("Failed to render mnemonic phrase: {}", e); return -2; }
+        };
+
+        match write_cstring_to_out(&phrase, mnemonic_out_ptr, out_buffer_len_c) {
+            Ok(len) => len,
+            Err(code) => code,
+        }
+    });
+    match result {
+        Ok(val) => val,
+        Err(_) => { eprintln!("Panic caught in generate_mnemonic_ffi"); -5 }
+    }
+}
+
+
+// ---------------------------------------------------------------------------
+// ECIES (secp256k1) hybrid encryption
+//
+// Follows the Parity/ethkey scheme: an ephemeral secp256k1 keypair, ECDH with
+// the peer key, a NIST SP 800-56 concatenation (SHA-256) KDF splitting the shared secret into a
+// 16-byte AES-128-CTR key and a SHA-256-folded HMAC key, then
+// `ephemeral_pubkey(65) || iv(16) || ciphertext || hmac_sha256(32)`.
+// ---------------------------------------------------------------------------
+
+type HmacSha256 = Hmac<Sha256>;
+
+// --- Helper: NIST SP 800-56 concatenation KDF with SHA-256 (counter, then secret) ---
+fn ansi_x963_kdf(shared_secret: &[u8], out_len: usize) -> Vec<u8> {
+    let mut key = Vec::with_capacity(out_len);
+    let mut counter: u32 = 1;
+    while key.len() < out_len {
+        let mut hasher = Sha256::new();
+        hasher.update(counter.to_be_bytes());
+        hasher.update(shared_secret);
+        key.extend_from_slice(&hasher.finalize());
+        counter += 1;
+    }
+    key.truncate(out_len);
+    key
+}
+
+// --- Helper: derive the AES-128 key and HMAC key from a shared secret ---
+fn ecies_derive_keys(shared_secret: &[u8]) -> ([u8; 16], [u8; 32]) {
+    let key_material = ansi_x963_kdf(shared_secret, 32);
+    let mut enc_key = [0u8; 16];
+    enc_key.copy_from_slice(&key_material[0..16]);
+    // The MAC key is SHA-256 of the second half of the derived material.
+    let mac_key: [u8; 32] = Sha256::digest(&key_material[16..32]).into();
+    (enc_key, mac_key)
+}
+
+fn ecies_encrypt(recipient_pubkey_bytes: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let recipient = PublicKey::from_sec1_bytes(recipient_pubkey_bytes)
+        .map_err(|e| format!("Invalid recipient public key: {}", e))?;
+
+    let ephemeral_secret = SecretKey::random(&mut OsRng);
+    let shared = diffie_hellman(ephemeral_secret.to_nonzero_scalar(), recipient.as_affine());
+    let (enc_key, mac_key) = ecies_derive_keys(shared.raw_secret_bytes());
+
+    let mut iv = [0u8; 16];
+    OsRng.fill_bytes(&mut iv);
+    let mut ciphertext = plaintext.to_vec();
+    let mut cipher = Aes128Ctr::new((&enc_key).into(), (&iv).into());
+    cipher.apply_keystream(&mut ciphertext);
+
+    // HMAC covers iv || ciphertext.
+    let mut mac = <HmacSha256 as Mac>::new_from_slice(&mac_key)
+        .map_err(|e| format!("HMAC init failed: {}", e))?;
+    mac.update(&iv);
+    mac.update(&ciphertext);
+    let tag = mac.finalize().into_bytes();
+
+    // Uncompressed SEC1 encoding (65 bytes) so the decrypt side can slice it by length.
+    let ephemeral_pubkey = ephemeral_secret.public_key().to_encoded_point(false).as_bytes().to_vec();
+    let mut out = Vec::with_capacity(ephemeral_pubkey.len() + iv.len() + ciphertext.len() + tag.len());
+    out.extend_from_slice(&ephemeral_pubkey);
+    out.extend_from_slice(&iv);
+    out.extend_from_slice(&ciphertext);
+    out.extend_from_slice(&tag);
+    Ok(out)
+}
+
+fn ecies_decrypt(our_secret_bytes: &[u8; 32], data: &[u8]) -> Result<Vec<u8>, String> {
+    // Uncompressed ephemeral pubkey (65) + iv (16) + tag (32) is the minimum envelope.
+    const EPH_LEN: usize = 65;
+    const IV_LEN: usize = 16;
+    const TAG_LEN: usize = 32;
+    if data.len() < EPH_LEN + IV_LEN + TAG_LEN {
+        return Err("ECIES ciphertext too short".to_string());
+    }
+    let ephemeral = PublicKey::from_sec1_bytes(&data[0..EPH_LEN])
+        .map_err(|e| format!("Invalid ephemeral public key: {}", e))?;
+    let iv = &data[EPH_LEN..EPH_LEN + IV_LEN];
+    let tag = &data[data.len() - TAG_LEN..];
+    let ciphertext = &data[EPH_LEN + IV_LEN..data.len() - TAG_LEN];
+
+    let our_secret = SecretKey::from_bytes(GenericArray::<u8, U32>::from_slice(our_secret_bytes))
+        .map_err(|e| format!("Invalid stored secret key: {}", e))?;
+    let shared = diffie_hellman(our_secret.to_nonzero_scalar(), ephemeral.as_affine());
+    let (enc_key, mac_key) = ecies_derive_keys(shared.raw_secret_bytes());
+
+    let mut mac = <HmacSha256 as Mac>::new_from_slice(&mac_key)
+        .map_err(|e| format!("HMAC init failed: {}", e))?;
+    mac.update(iv);
+    mac.update(ciphertext);
+    let expected = mac.finalize().into_bytes();
+    if !constant_time_eq(&expected, tag) {
+        return Err("ECIES MAC mismatch".to_string());
+    }
+
+    let mut plaintext = ciphertext.to_vec();
+    let mut cipher = Aes128Ctr::new((&enc_key).into(), iv.into());
+    cipher.apply_keystream(&mut plaintext);
+    Ok(plaintext)
+}
+
+/// Encrypt `plaintext_bytes[..len]` to the secp256k1 public key `recipient_pubkey_hex`
+/// (uncompressed or compressed SEC1, 0x-prefixed or bare) using ECIES. Writes the
+/// 0x-prefixed hex envelope (ephemeral pubkey || iv || ciphertext || mac).
+#[no_mangle]
+pub extern "C" fn ecies_encrypt_ffi(
+    recipient_pubkey_hex_cchar: *const c_char,
+    plaintext_bytes: *const u8,
+    plaintext_len_c: c_ulonglong,
+    ciphertext_hex_out_ptr: *mut c_char,
+    out_buffer_len_c: c_uint,
+) -> c_int {
+    let result = panic::catch_unwind(|| {
+        let recipient_hex = match c_str_to_string(recipient_pubkey_hex_cchar) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("Error converting recipient pubkey: {}", e); return -4; }
+        };
+        if plaintext_bytes.is_null() {
+            eprintln!("Null pointer passed for plaintext bytes.");
+            return -4;
+        }
+        let plaintext = unsafe { std::slice::from_raw_parts(plaintext_bytes, plaintext_len_c as usize) };
+        let recipient_bytes = match hex::decode(recipient_hex.strip_prefix("0x").unwrap_or(&recipient_hex)) {
+            Ok(b) => b,
+            Err(e) => { eprintln!("Error decoding recipient pubkey hex: {}", e); return -4; }
+        };
+
+        let envelope = match ecies_encrypt(&recipient_bytes, plaintext) {
+            Ok(ct) => ct,
+            Err(e) => { eprintln!("ECIES encryption failed: {}", e); return -18; }
+        };
+        let envelope_hex = format!("0x{}", hex::encode(envelope));
+
+        match write_cstring_to_out(&envelope_hex, ciphertext_hex_out_ptr, out_buffer_len_c) {
+            Ok(len) => len,
+            Err(code) => code,
+        }
+    });
+    match result {
+        Ok(val) => val,
+        Err(_) => { eprintln!("Panic caught in ecies_encrypt_ffi"); -5 }
+    }
+}
+
+/// Decrypt an ECIES envelope `ciphertext_bytes[..len]` with the stored key
+/// `key_identifier` (decrypted using `master_password`). Writes the recovered
+/// plaintext as a 0x-prefixed hex string.
+#[no_mangle]
+pub extern "C" fn ecies_decrypt_ffi(
+    key_identifier_cchar: *const c_char,
+    master_password_cchar: *const c_char,
+    ciphertext_bytes: *const u8,
+    ciphertext_len_c: c_ulonglong,
+    plaintext_hex_out_ptr: *mut c_char,
+    out_buffer_len_c: c_uint,
+) -> c_int {
+    let result = panic::catch_unwind(|| {
+        let key_id = match c_str_to_string(key_identifier_cchar) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("Error converting key_identifier: {}", e); return -11; }
+        };
+        let master_password = match c_str_to_string(master_password_cchar) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("Error converting master_password: {}", e); return -12; }
+        };
+        if ciphertext_bytes.is_null() {
+            eprintln!("Null pointer passed for ciphertext bytes.");
+            return -4;
+        }
+        let envelope = unsafe { std::slice::from_raw_parts(ciphertext_bytes, ciphertext_len_c as usize) };
+
+        let pk_bytes = match load_and_decrypt_pk(&key_id, &master_password) {
+            Ok(pk) => pk,
+            Err(e) => { eprintln!("Failed to load/decrypt private key for '{}': {}", key_id, e); return -1; }
+        };
+        let plaintext = match ecies_decrypt(&pk_bytes, envelope) {
+            Ok(pt) => pt,
+            Err(e) => { eprintln!("ECIES decryption failed: {}", e); return -18; }
+        };
+        let plaintext_hex = format!("0x{}", hex::encode(plaintext));
+
+        match write_cstring_to_out(&plaintext_hex, plaintext_hex_out_ptr, out_buffer_len_c) {
+            Ok(len) => len,
+            Err(code) => code,
+        }
+    });
+    match result {
+        Ok(val) => val,
+        Err(_) => { eprintln!("Panic caught in ecies_decrypt_ffi"); -5 }
+    }
+}
+
+
+/// Search for a key whose address begins with `address_prefix_hex` (case-insensitive,
+/// 0x-prefixed or bare), trying up to `max_attempts` random keys, then store the match
+/// under `key_identifier` via the existing encrypt-and-write path. Returns `-19` if the
+/// attempt budget is exhausted without a match.
+#[no_mangle]
+pub extern "C" fn store_vanity_key_ffi(
+    key_identifier_cchar: *const c_char,
+    address_prefix_hex_cchar: *const c_char,
+    max_attempts_c: c_ulonglong,
+    master_password_cchar: *const c_char,
+) -> c_int {
+    let result = panic::catch_unwind(|| {
+        let key_id = match c_str_to_string(key_identifier_cchar) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("Error converting key_identifier for vanity store: {}", e); return -11; }
+        };
+        let prefix_raw = match c_str_to_string(address_prefix_hex_cchar) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("Error converting address prefix: {}", e); return -4; }
+        };
+        let master_password = match c_str_to_string(master_password_cchar) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("Error converting master_password for vanity store: {}", e); return -12; }
+        };
+
+        let prefix = prefix_raw.strip_prefix("0x").unwrap_or(&prefix_raw).to_lowercase();
+        if prefix.is_empty() || !prefix.chars().all(|c| c.is_ascii_hexdigit()) {
+            eprintln!("Invalid address prefix: '{}'", prefix_raw);
+            return -4;
+        }
+
+        // Fail early if the identifier is already taken, before spending search effort.
+        let key_store_path = match get_key_storage_path() {
+            Ok(path) => path,
+            Err(e) => { eprintln!("Failed to get key_storage_path for vanity store: {}", e); return -15; }
+        };
+        let key_file_path = key_store_path.join(format!("{}.json", key_id));
+        if key_file_path.exists() {
+            eprintln!("Key file already exists for identifier: {}. Will not overwrite.", key_id);
+            return -16;
+        }
+
+        let mut pk_bytes: Option<[u8; 32]> = None;
+        for _ in 0..max_attempts_c {
+            let candidate = SigningKey::random(&mut OsRng);
+            let address = LocalWallet::from(candidate.clone()).address();
+            if hex::encode(address.as_bytes()).starts_with(&prefix) {
+                pk_bytes = Some(candidate.to_bytes().into());
+                break;
+            }
+        }
+        let pk_bytes = match pk_bytes {
+            Some(pk) => pk,
+            None => {
+                eprintln!("Vanity search exhausted {} attempts without a match for prefix '{}'", max_attempts_c, prefix);
+                return -19;
+            }
+        };
+
+        let encrypted_file_data = match encrypt_pk_and_prepare_file_data(&pk_bytes, &master_password) {
+            Ok(data) => data,
+            Err(e) => { eprintln!("Failed to encrypt vanity key for '{}': {}", key_id, e); return -13; }
+        };
+        let json_data = match serde_json::to_string_pretty(&encrypted_file_data) {
+            Ok(json) => json,
+            Err(e) => { eprintln!("Failed to serialize vanity key data for '{}': {}", key_id, e); return -14; }
+        };
+        match write_key_file_atomic(&key_file_path, &json_data) {
+            Ok(_) => {
+                println!("Successfully stored vanity key for identifier: {}", key_id);
+                0
+            }
+            Err(e) => { eprintln!("Failed to write vanity key file for '{}': {}", key_id, e); -17 }
+        }
+    });
+    match result {
+        Ok(val) => val,
+        Err(_) => { eprintln!("Panic caught in store_vanity_key_ffi"); -5 }
+    }
+}
+
+
+/// List the identifiers of every stored key, written to the output buffer as a
+/// comma-separated string (empty string if the store has no keys).
+#[no_mangle]
+pub extern "C" fn list_keys_ffi(
+    identifiers_out_ptr: *mut c_char,
+    out_buffer_len_c: c_uint,
+) -> c_int {
+    let result = panic::catch_unwind(|| {
+        let key_store_path = match get_key_storage_path() {
+            Ok(path) => path,
+            Err(e) => { eprintln!("Failed to get key_storage_path for list: {}", e); return -15; }
+        };
+        let entries = match fs::read_dir(&key_store_path) {
+            Ok(entries) => entries,
+            Err(e) => { eprintln!("Failed to read key store directory: {}", e); return -17; }
+        };
+        let mut identifiers = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) == Some("json") {
+                if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                    identifiers.push(stem.to_string());
+                }
+            }
+        }
+        identifiers.sort();
+        let joined = identifiers.join(",");
+
+        match write_cstring_to_out(&joined, identifiers_out_ptr, out_buffer_len_c) {
+            Ok(len) => len,
+            Err(code) => code,
+        }
+    });
+    match result {
+        Ok(val) => val,
+        Err(_) => { eprintln!("Panic caught in list_keys_ffi"); -5 }
+    }
+}
+
+/// Delete the stored key `key_identifier`. Returns `-16` if no such key exists.
+#[no_mangle]
+pub extern "C" fn delete_key_ffi(
+    key_identifier_cchar: *const c_char,
+) -> c_int {
+    let result = panic::catch_unwind(|| {
+        let key_id = match c_str_to_string(key_identifier_cchar) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("Error converting key_identifier for delete: {}", e); return -11; }
+        };
+        let key_store_path = match get_key_storage_path() {
+            Ok(path) => path,
+            Err(e) => { eprintln!("Failed to get key_storage_path for delete: {}", e); return -15; }
+        };
+        let key_file_path = key_store_path.join(format!("{}.json", key_id));
+        if !key_file_path.exists() {
+            eprintln!("Key file not found for identifier: {}", key_id);
+            return -16;
+        }
+        match fs::remove_file(&key_file_path) {
+            Ok(_) => {
+                println!("Successfully deleted key for identifier: {}", key_id);
+                0
+            }
+            Err(e) => { eprintln!("Failed to delete key file for '{}': {}", key_id, e); -17 }
+        }
+    });
+    match result {
+        Ok(val) => val,
+        Err(_) => { eprintln!("Panic caught in delete_key_ffi"); -5 }
+    }
+}
+
+/// Re-encrypt the stored key `key_identifier` under a new master password: decrypt with
+/// `old_password`, then re-encrypt (fresh Argon2 salt and AES nonce) with `new_password`,
+/// written atomically in place.
+#[no_mangle]
+pub extern "C" fn change_password_ffi(
+    key_identifier_cchar: *const c_char,
+    old_password_cchar: *const c_char,
+    new_password_cchar: *const c_char,
+) -> c_int {
+    let result = panic::catch_unwind(|| {
+        let key_id = match c_str_to_string(key_identifier_cchar) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("Error converting key_identifier for change_password: {}", e); return -11; }
+        };
+        let old_password = match c_str_to_string(old_password_cchar) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("Error converting old password: {}", e); return -12; }
+        };
+        let new_password = match c_str_to_string(new_password_cchar) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("Error converting new password: {}", e); return -12; }
+        };
+
+        let pk_bytes = match load_and_decrypt_pk(&key_id, &old_password) {
+            Ok(pk) => pk,
+            Err(e) => { eprintln!("Failed to load/decrypt key for '{}': {}", key_id, e); return -1; }
+        };
+        let encrypted_file_data = match encrypt_pk_and_prepare_file_data(&pk_bytes, &new_password) {
+            Ok(data) => data,
+            Err(e) => { eprintln!("Failed to re-encrypt key for '{}': {}", key_id, e); return -13; }
+        };
+        let json_data = match serde_json::to_string_pretty(&encrypted_file_data) {
+            Ok(json) => json,
+            Err(e) => { eprintln!("Failed to serialize re-encrypted key data for '{}': {}", key_id, e); return -14; }
+        };
+        let key_store_path = match get_key_storage_path() {
+            Ok(path) => path,
+            Err(e) => { eprintln!("Failed to get key_storage_path for change_password: {}", e); return -15; }
+        };
+        let key_file_path = key_store_path.join(format!("{}.json", key_id));
+
+        match write_key_file_atomic(&key_file_path, &json_data) {
+            Ok(_) => {
+                println!("Successfully changed password for identifier: {}", key_id);
+                0
+            }
+            Err(e) => { eprintln!("Failed to write re-encrypted key file for '{}': {}", key_id, e); -17 }
+        }
+    });
+    match result {
+        Ok(val) => val,
+        Err(_) => { eprintln!("Panic caught in change_password_ffi"); -5 }
+    }
+}
+
+
 #[no_mangle]
 pub extern "C" fn rust_lib_health_check() -> c_int {
     println!("Rust library 'rust_juliaos_signer' is alive and reachable!");